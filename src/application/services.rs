@@ -0,0 +1,123 @@
+use crate::domain::error::AppError;
+use crate::domain::models::{LoginResponse, NewUserObject, Page, Pagination, UserFilter, UserObject};
+use crate::domain::repository::{AvatarRepository, UserRepository};
+use image::imageops::FilterType;
+use image::ImageFormat;
+use std::io::Cursor;
+
+/// Avatars are normalized to this square thumbnail size before storage.
+const AVATAR_SIZE: u32 = 256;
+
+/// Source images wider or taller than this are rejected outright, so a
+/// malicious caller can't force us to decode an unbounded image into memory.
+const MAX_SOURCE_DIMENSION: u32 = 4096;
+
+pub struct UserService<'a, R: UserRepository> {
+    repo: &'a mut R,
+}
+
+impl<'a, R: UserRepository> UserService<'a, R> {
+    pub fn new(repo: &'a mut R) -> Self {
+        Self { repo }
+    }
+
+    pub fn create_user(&mut self, new_user: NewUserObject) -> Result<UserObject, AppError> {
+        self.repo.create_user(new_user)
+    }
+
+    pub fn get_user_by_id(&mut self, user_id: i32) -> Result<UserObject, AppError> {
+        self.repo.get_user_by_id(user_id)
+    }
+
+    pub fn list_users(
+        &mut self,
+        pagination: Pagination,
+        filter: UserFilter,
+    ) -> Result<Page<UserObject>, AppError> {
+        self.repo.list_users(pagination, filter)
+    }
+
+    pub fn get_user_by_email(&mut self, email: &str) -> Result<UserObject, AppError> {
+        self.repo.get_user_by_email(email)
+    }
+
+    pub fn delete_user(&mut self, user_id: i32) -> Result<usize, AppError> {
+        self.repo.delete_user(user_id)
+    }
+
+    pub fn update_user(
+        &mut self,
+        user_object: UserObject,
+        new_password: Option<&str>,
+    ) -> Result<UserObject, AppError> {
+        self.repo.update_user(user_object, new_password)
+    }
+
+    pub fn login(&mut self, email: &str, password: &str) -> Result<LoginResponse, AppError> {
+        self.repo.login(email, password)
+    }
+
+    pub fn refresh_access_token(&mut self, refresh_token: &str) -> Result<String, AppError> {
+        self.repo.refresh_access_token(refresh_token)
+    }
+
+    pub fn logout(&mut self, refresh_token: &str) -> Result<(), AppError> {
+        self.repo.logout(refresh_token)
+    }
+
+    pub fn request_password_reset(&mut self, email: &str) -> Result<(), AppError> {
+        self.repo.request_password_reset(email)
+    }
+
+    pub fn reset_password(&mut self, token: &str, new_password: &str) -> Result<(), AppError> {
+        self.repo.reset_password(token, new_password)
+    }
+}
+
+pub struct AvatarService<'a, R: AvatarRepository> {
+    repo: &'a mut R,
+}
+
+impl<'a, R: AvatarRepository> AvatarService<'a, R> {
+    pub fn new(repo: &'a mut R) -> Self {
+        Self { repo }
+    }
+
+    pub fn get_avatar(&mut self, user_id: i32) -> Result<(Vec<u8>, String), AppError> {
+        self.repo.get_avatar(user_id)
+    }
+
+    /// Decodes `raw_image`, rejects anything undecodable or oversized, then
+    /// crops/resizes it to a square thumbnail and re-encodes it to PNG
+    /// before handing it to the repository to store. Dimensions are read
+    /// from the header via `into_dimensions` and checked *before* the full
+    /// decode, so an oversized image is rejected without ever being
+    /// fully decompressed into memory.
+    pub fn upload_avatar(&mut self, user_id: i32, raw_image: &[u8]) -> Result<(), AppError> {
+        let undecodable = || AppError::Validation("uploaded file is not a decodable image".into());
+
+        let (width, height) = image::io::Reader::new(Cursor::new(raw_image))
+            .with_guessed_format()
+            .map_err(|_| undecodable())?
+            .into_dimensions()
+            .map_err(|_| undecodable())?;
+
+        if width > MAX_SOURCE_DIMENSION || height > MAX_SOURCE_DIMENSION {
+            return Err(AppError::Validation(format!(
+                "image dimensions must not exceed {MAX_SOURCE_DIMENSION}x{MAX_SOURCE_DIMENSION}"
+            )));
+        }
+
+        let decoded = image::load_from_memory(raw_image).map_err(|_| undecodable())?;
+
+        let thumbnail = decoded.resize_to_fill(AVATAR_SIZE, AVATAR_SIZE, FilterType::Lanczos3);
+
+        let mut png_bytes = Cursor::new(Vec::new());
+        thumbnail
+            .write_to(&mut png_bytes, ImageFormat::Png)
+            .map_err(|_| AppError::Validation("failed to re-encode image".into()))?;
+
+        self.repo
+            .upsert_avatar(user_id, png_bytes.into_inner(), "image/png")
+    }
+}
@@ -0,0 +1,92 @@
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde::Serialize;
+use std::fmt;
+
+/// Domain-wide error type. Every repository and service method returns this
+/// instead of leaking `diesel::result::Error`, so handlers can just `?` and
+/// let `ResponseError` turn it into the right HTTP status and JSON body.
+#[derive(Debug)]
+pub enum AppError {
+    NotFound,
+    Unauthorized,
+    Forbidden,
+    Validation(String),
+    Conflict,
+    Database(DieselError),
+    Hashing,
+    /// Infrastructure failure unrelated to a specific domain operation, e.g.
+    /// failing to check out a connection from the pool.
+    Internal,
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NotFound => write!(f, "resource not found"),
+            AppError::Unauthorized => write!(f, "unauthorized"),
+            AppError::Forbidden => write!(f, "missing required permission"),
+            AppError::Validation(message) => write!(f, "{}", message),
+            AppError::Conflict => write!(f, "resource already exists"),
+            AppError::Database(e) => write!(f, "database error: {}", e),
+            AppError::Hashing => write!(f, "failed to hash password"),
+            AppError::Internal => write!(f, "internal server error"),
+        }
+    }
+}
+
+impl From<DieselError> for AppError {
+    fn from(error: DieselError) -> Self {
+        match error {
+            DieselError::NotFound => AppError::NotFound,
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => AppError::Conflict,
+            other => AppError::Database(other),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: ErrorDetails,
+}
+
+#[derive(Serialize)]
+struct ErrorDetails {
+    code: &'static str,
+    message: String,
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::NotFound => StatusCode::NOT_FOUND,
+            AppError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden => StatusCode::FORBIDDEN,
+            AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::Conflict => StatusCode::CONFLICT,
+            AppError::Database(_) | AppError::Hashing | AppError::Internal => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let code = match self {
+            AppError::NotFound => "not_found",
+            AppError::Unauthorized => "unauthorized",
+            AppError::Forbidden => "forbidden",
+            AppError::Validation(_) => "validation",
+            AppError::Conflict => "conflict",
+            AppError::Database(_) => "database",
+            AppError::Hashing => "hashing",
+            AppError::Internal => "internal",
+        };
+
+        HttpResponse::build(self.status_code()).json(ErrorBody {
+            error: ErrorDetails {
+                code,
+                message: self.to_string(),
+            },
+        })
+    }
+}
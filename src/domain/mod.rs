@@ -0,0 +1,3 @@
+pub mod error;
+pub mod models;
+pub mod repository;
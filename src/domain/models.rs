@@ -0,0 +1,128 @@
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, ToSchema)]
+pub struct UserObject {
+    pub id: i32,
+    pub pseudo: Option<String>,
+    pub email: Option<String>,
+    #[serde(skip_serializing)]
+    pub password_hash: Option<String>,
+    pub role: Option<String>,
+    pub has_avatar: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, ToSchema)]
+#[diesel(table_name = crate::schema::user)]
+pub struct NewUserObject {
+    pub pseudo: Option<String>,
+    pub email: Option<String>,
+    pub password_hash: Option<String>,
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub role: String,
+    pub iat: usize,
+    pub exp: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LoginResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RefreshResponse {
+    pub access_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LogoutRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+/// Default number of rows returned by `/user/list` when `limit` is omitted.
+pub const DEFAULT_PAGE_LIMIT: i64 = 20;
+
+/// Upper bound on `limit`, regardless of what the caller asks for.
+pub const MAX_PAGE_LIMIT: i64 = 100;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Pagination {
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl Pagination {
+    pub fn new(limit: Option<i64>, offset: Option<i64>) -> Self {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT);
+        let offset = offset.unwrap_or(0).max(0);
+
+        Self { limit, offset }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total_count: i64,
+    pub has_next: bool,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total_count: i64, pagination: Pagination) -> Self {
+        let has_next = pagination.offset + (items.len() as i64) < total_count;
+
+        Self {
+            items,
+            total_count,
+            has_next,
+        }
+    }
+}
+
+/// Optional substring filters applied to `/user/list` with `ILIKE`.
+#[derive(Debug, Clone, Default)]
+pub struct UserFilter {
+    pub pseudo: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, ToSchema)]
+pub struct RoleObject {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, ToSchema)]
+pub struct PermissionObject {
+    pub id: i32,
+    pub name: String,
+}
@@ -0,0 +1,77 @@
+use crate::domain::error::AppError;
+use crate::domain::models::{LoginResponse, NewUserObject, Page, Pagination, UserFilter, UserObject};
+
+pub trait RoleRepository {
+    /// Loads the set of permission names granted to a role by name,
+    /// expanding `role_permission` in a single join query.
+    fn get_permissions_for_role(&mut self, role_name: &str) -> Result<Vec<String>, AppError>;
+
+    /// Creates the role if it does not already exist and returns its id.
+    fn get_or_create_role(&mut self, role_name: &str) -> Result<i32, AppError>;
+
+    /// Creates the permission if it does not already exist and returns its id.
+    fn get_or_create_permission(&mut self, permission_name: &str) -> Result<i32, AppError>;
+
+    /// Grants a permission to a role, no-op if the pair already exists.
+    fn grant_permission(&mut self, role_id: i32, permission_id: i32) -> Result<(), AppError>;
+}
+
+pub trait UserRepository {
+    fn create_user(&mut self, new_user: NewUserObject) -> Result<UserObject, AppError>;
+    fn get_user_by_id(&mut self, user_id: i32) -> Result<UserObject, AppError>;
+
+    /// Paginated, optionally filtered user listing. `filter` fields are
+    /// matched as case-insensitive substrings (`ILIKE '%value%'`).
+    fn list_users(
+        &mut self,
+        pagination: Pagination,
+        filter: UserFilter,
+    ) -> Result<Page<UserObject>, AppError>;
+
+    fn get_user_by_email(&mut self, email: &str) -> Result<UserObject, AppError>;
+    fn delete_user(&mut self, user_id: i32) -> Result<usize, AppError>;
+
+    /// Updates the given user's fields. `new_password`, when present, is a
+    /// plaintext password to hash and store; when absent, the user's existing
+    /// password hash is kept as-is rather than being re-hashed.
+    fn update_user(
+        &mut self,
+        user_object: UserObject,
+        new_password: Option<&str>,
+    ) -> Result<UserObject, AppError>;
+
+    /// Verifies credentials and issues a fresh access/refresh token pair,
+    /// persisting the refresh token so it can later be revoked.
+    fn login(&mut self, email: &str, password: &str) -> Result<LoginResponse, AppError>;
+
+    /// Validates an unexpired, unrevoked refresh token and issues a new
+    /// short-lived access token without requiring the password again.
+    fn refresh_access_token(&mut self, refresh_token: &str) -> Result<String, AppError>;
+
+    /// Revokes a refresh token so it can no longer be used to mint access tokens.
+    fn logout(&mut self, refresh_token: &str) -> Result<(), AppError>;
+
+    /// Issues a single-use, 1-hour password reset token for the given email if
+    /// an account exists, storing only its hash. Returns `Ok(())` when the
+    /// email is unknown, just as for a known one, so callers can't use this to
+    /// enumerate accounts; database failures are returned as errors.
+    fn request_password_reset(&mut self, email: &str) -> Result<(), AppError>;
+
+    /// Consumes a password reset token and sets the account's new password.
+    /// Fails if the token is unknown, expired, or already consumed.
+    fn reset_password(&mut self, token: &str, new_password: &str) -> Result<(), AppError>;
+}
+
+pub trait AvatarRepository {
+    /// Fetches the stored avatar bytes and their content type. Returns
+    /// `AppError::NotFound` if the user has no avatar on file.
+    fn get_avatar(&mut self, user_id: i32) -> Result<(Vec<u8>, String), AppError>;
+
+    /// Stores (or replaces) a user's avatar and flags `user.has_avatar`.
+    fn upsert_avatar(
+        &mut self,
+        user_id: i32,
+        image_data: Vec<u8>,
+        content_type: &str,
+    ) -> Result<(), AppError>;
+}
@@ -0,0 +1,60 @@
+use crate::domain::{error::AppError, repository::AvatarRepository};
+use diesel::result::Error;
+use diesel::PgConnection;
+use diesel::{
+    r2d2::{self, ConnectionManager},
+    Connection, ExpressionMethods, QueryDsl, RunQueryDsl,
+};
+use std::time::SystemTime;
+
+pub struct PostgresAvatarRepository<'a> {
+    pub conn: &'a mut r2d2::PooledConnection<ConnectionManager<PgConnection>>,
+}
+
+impl<'a> AvatarRepository for PostgresAvatarRepository<'a> {
+    fn get_avatar(&mut self, p_user_id: i32) -> Result<(Vec<u8>, String), AppError> {
+        use crate::schema::avatar::dsl::*;
+
+        avatar
+            .filter(user_id.eq(p_user_id))
+            .select((image_data, content_type))
+            .first::<(Vec<u8>, String)>(self.conn)
+            .map_err(AppError::from)
+    }
+
+    fn upsert_avatar(
+        &mut self,
+        p_user_id: i32,
+        p_image_data: Vec<u8>,
+        p_content_type: &str,
+    ) -> Result<(), AppError> {
+        use crate::schema::avatar::dsl as avatar_dsl;
+        use crate::schema::user::dsl as user_dsl;
+
+        self.conn
+            .transaction::<_, Error, _>(|conn| {
+                diesel::insert_into(avatar_dsl::avatar)
+                    .values((
+                        avatar_dsl::user_id.eq(p_user_id),
+                        avatar_dsl::image_data.eq(&p_image_data),
+                        avatar_dsl::content_type.eq(p_content_type),
+                        avatar_dsl::updated_at.eq(SystemTime::now()),
+                    ))
+                    .on_conflict(avatar_dsl::user_id)
+                    .do_update()
+                    .set((
+                        avatar_dsl::image_data.eq(&p_image_data),
+                        avatar_dsl::content_type.eq(p_content_type),
+                        avatar_dsl::updated_at.eq(SystemTime::now()),
+                    ))
+                    .execute(conn)?;
+
+                diesel::update(user_dsl::user.filter(user_dsl::id.eq(p_user_id)))
+                    .set(user_dsl::has_avatar.eq(true))
+                    .execute(conn)?;
+
+                Ok(())
+            })
+            .map_err(AppError::from)
+    }
+}
@@ -0,0 +1,4 @@
+pub mod avatar_repository;
+pub mod role_repository;
+pub mod seed;
+pub mod user_repository;
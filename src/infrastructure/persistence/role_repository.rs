@@ -0,0 +1,81 @@
+use crate::domain::error::AppError;
+use crate::domain::repository::RoleRepository;
+use diesel::PgConnection;
+use diesel::{
+    r2d2::{self, ConnectionManager},
+    ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
+};
+
+pub struct PostgresRoleRepository<'a> {
+    pub conn: &'a mut r2d2::PooledConnection<ConnectionManager<PgConnection>>,
+}
+
+impl<'a> RoleRepository for PostgresRoleRepository<'a> {
+    fn get_permissions_for_role(&mut self, role_name: &str) -> Result<Vec<String>, AppError> {
+        use crate::schema::permission::dsl as permission_dsl;
+        use crate::schema::role::dsl as role_dsl;
+        use crate::schema::role_permission::dsl as role_permission_dsl;
+
+        role_dsl::role
+            .filter(role_dsl::name.eq(role_name))
+            .inner_join(
+                role_permission_dsl::role_permission
+                    .on(role_permission_dsl::role_id.eq(role_dsl::id)),
+            )
+            .inner_join(
+                permission_dsl::permission.on(permission_dsl::id.eq(role_permission_dsl::permission_id)),
+            )
+            .select(permission_dsl::name)
+            .load::<String>(self.conn)
+            .map_err(AppError::from)
+    }
+
+    fn get_or_create_role(&mut self, role_name: &str) -> Result<i32, AppError> {
+        use crate::schema::role::dsl::*;
+
+        let existing = role
+            .filter(name.eq(role_name))
+            .select(id)
+            .first::<i32>(self.conn)
+            .optional()?;
+
+        match existing {
+            Some(existing_id) => Ok(existing_id),
+            None => diesel::insert_into(role)
+                .values(name.eq(role_name))
+                .returning(id)
+                .get_result::<i32>(self.conn)
+                .map_err(AppError::from),
+        }
+    }
+
+    fn get_or_create_permission(&mut self, permission_name: &str) -> Result<i32, AppError> {
+        use crate::schema::permission::dsl::*;
+
+        let existing = permission
+            .filter(name.eq(permission_name))
+            .select(id)
+            .first::<i32>(self.conn)
+            .optional()?;
+
+        match existing {
+            Some(existing_id) => Ok(existing_id),
+            None => diesel::insert_into(permission)
+                .values(name.eq(permission_name))
+                .returning(id)
+                .get_result::<i32>(self.conn)
+                .map_err(AppError::from),
+        }
+    }
+
+    fn grant_permission(&mut self, p_role_id: i32, p_permission_id: i32) -> Result<(), AppError> {
+        use crate::schema::role_permission::dsl::*;
+
+        diesel::insert_into(role_permission)
+            .values((role_id.eq(p_role_id), permission_id.eq(p_permission_id)))
+            .on_conflict_do_nothing()
+            .execute(self.conn)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use crate::domain::error::AppError;
+use crate::domain::repository::RoleRepository;
+use crate::infrastructure::persistence::role_repository::PostgresRoleRepository;
+use diesel::PgConnection;
+use diesel::r2d2::{self, ConnectionManager};
+
+/// Names of the permissions granted to the built-in `admin` role. Run once at
+/// boot so a fresh database always has a usable super-user role.
+const ADMIN_PERMISSIONS: &[&str] = &["user.create", "user.delete", "user.list", "user.avatar.write"];
+
+pub fn seed_admin_role(
+    conn: &mut r2d2::PooledConnection<ConnectionManager<PgConnection>>,
+) -> Result<(), AppError> {
+    let mut role_repo = PostgresRoleRepository { conn };
+
+    let admin_role_id = role_repo.get_or_create_role("admin")?;
+
+    for permission_name in ADMIN_PERMISSIONS {
+        let permission_id = role_repo.get_or_create_permission(permission_name)?;
+        role_repo.grant_permission(admin_role_id, permission_id)?;
+    }
+
+    Ok(())
+}
@@ -1,151 +1,362 @@
-use crate::domain::{
-    models::{Claims, NewUserObject, UserObject},
-    repository::UserRepository,
-};
-use diesel::result::Error;
-use diesel::PgConnection;
-use diesel::{
-    r2d2::{self, ConnectionManager},
-    ExpressionMethods, QueryDsl, RunQueryDsl,
-};
-use dotenvy::dotenv;
-use jsonwebtoken::{encode, EncodingKey, Header};
-use std::env;
-
-pub struct PostgresUserRepository<'a> {
-    pub conn: &'a mut r2d2::PooledConnection<ConnectionManager<PgConnection>>,
-}
-
-impl<'a> UserRepository for PostgresUserRepository<'a> {
-    fn create_user(&mut self, new_user: NewUserObject) -> Result<UserObject, Error> {
-        use crate::schema::user::dsl::*;
-
-        // Hash the password
-        let hashed_password = bcrypt::hash(
-            new_user.password_hash.as_ref().unwrap(),
-            bcrypt::DEFAULT_COST,
-        )
-        .expect("Failed to hash password");
-
-        let new_user = NewUserObject {
-            pseudo: new_user.pseudo.clone(),
-            email: new_user.email.clone(),
-            password_hash: Some(hashed_password),
-            role: new_user.role.clone(),
-        };
-
-        let result = diesel::insert_into(user)
-            .values(&new_user)
-            .returning((id, pseudo, email, password_hash, role))
-            .get_result::<UserObject>(self.conn);
-
-        match result {
-            Ok(user_object) => Ok(user_object),
-            Err(e) => Err(e),
-        }
-    }
-
-    fn get_user_by_id(&mut self, user_id: i32) -> Result<UserObject, Error> {
-        use crate::schema::user::dsl::*;
-
-        let result = user
-            .filter(id.eq(user_id))
-            .select((id, pseudo, email, password_hash, role))
-            .first::<UserObject>(self.conn);
-
-        match result {
-            Ok(user_object) => Ok(user_object),
-            Err(e) => Err(e),
-        }
-    }
-
-    fn get_all_users(&mut self) -> Result<Vec<UserObject>, Error> {
-        use crate::schema::user::dsl::*;
-
-        let result = user
-            .select((id, pseudo, email, password_hash, role))
-            .load::<UserObject>(self.conn);
-
-        match result {
-            Ok(users) => Ok(users),
-            Err(e) => Err(e),
-        }
-    }
-
-    fn delete_user(&mut self, user_id: i32) -> Result<usize, Error> {
-        use crate::schema::user::dsl::*;
-
-        let result = diesel::delete(user.filter(id.eq(user_id))).execute(self.conn);
-
-        match result {
-            Ok(count) => Ok(count),
-            Err(e) => Err(e),
-        }
-    }
-
-    fn update_user(&mut self, user_object: UserObject) -> Result<UserObject, Error> {
-        use crate::schema::user::dsl::*;
-
-        // Hash the password
-        let hashed_password = bcrypt::hash(
-            user_object.password_hash.as_ref().unwrap(),
-            bcrypt::DEFAULT_COST,
-        )
-        .expect("Failed to hash password");
-
-        let result = diesel::update(user.filter(id.eq(user_object.id)))
-            .set((
-                pseudo.eq(user_object.pseudo.as_ref()),
-                email.eq(user_object.email.as_ref()),
-                password_hash.eq(Some(hashed_password)),
-                role.eq(user_object.role.as_ref()),
-            ))
-            .returning((id, pseudo, email, password_hash, role))
-            .get_result::<UserObject>(self.conn);
-
-        match result {
-            Ok(user_object) => Ok(user_object),
-            Err(e) => Err(e),
-        }
-    }
-
-    fn login(&mut self, p_email: &str, p_password: &str) -> Result<String, Error> {
-        use crate::schema::user::dsl::*;
-
-        // Load environment variables from .env file
-        dotenv().ok();
-        let secret_key = env::var(
-            "
-        JWT_SECRET",
-        )
-        .expect("JWT_SECRET must be set");
-
-        let result = user
-            .filter(email.eq(p_email))
-            .select((id, pseudo, email, password_hash, role))
-            .first::<UserObject>(self.conn);
-
-        match result {
-            Ok(user_object) => {
-                if bcrypt::verify(p_password, user_object.password_hash.as_ref().unwrap())
-                    .expect("Failed to verify password")
-                {
-                    let claims = Claims {
-                        sub: user_object.email.unwrap(),
-                        exp: 10000000000, // Set expiration as needed
-                    };
-                    let token = encode(
-                        &Header::default(),
-                        &claims,
-                        &EncodingKey::from_secret(secret_key.as_ref()),
-                    )
-                    .map_err(|_| Error::NotFound)?;
-                    Ok(token)
-                } else {
-                    Err(Error::NotFound)
-                }
-            }
-            Err(e) => Err(e),
-        }
-    }
-}
+use crate::config::AppConfig;
+use crate::domain::{
+    error::AppError,
+    models::{Claims, LoginResponse, NewUserObject, Page, Pagination, UserFilter, UserObject},
+    repository::UserRepository,
+};
+use diesel::result::Error;
+use diesel::PgConnection;
+use diesel::{
+    r2d2::{self, ConnectionManager},
+    Connection, ExpressionMethods, OptionalExtension, PgTextExpressionMethods, QueryDsl,
+    RunQueryDsl,
+};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long an issued refresh token remains usable to mint new access tokens.
+const REFRESH_TOKEN_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Role assigned to every account created through `create_user`, regardless
+/// of what the caller put in `NewUserObject.role` - `POST /user/create` is
+/// unauthenticated, so trusting that field would let anyone self-register
+/// as `admin` and walk straight through `require_permission`.
+const DEFAULT_USER_ROLE: &str = "user";
+
+/// How long a password reset token stays valid before it must be re-requested.
+const PASSWORD_RESET_TOKEN_TTL: Duration = Duration::from_secs(60 * 60);
+
+pub struct PostgresUserRepository<'a> {
+    pub conn: &'a mut r2d2::PooledConnection<ConnectionManager<PgConnection>>,
+    pub config: &'a AppConfig,
+}
+
+/// Hashes a plaintext password with bcrypt, shared by user creation, updates
+/// and password reset so they can never drift out of sync.
+fn hash_password(password: &str, bcrypt_cost: u32) -> Result<String, AppError> {
+    bcrypt::hash(password, bcrypt_cost).map_err(|_| AppError::Hashing)
+}
+
+/// Hashes an opaque random token with SHA-256 so the raw, still-valid value
+/// never sits in the database - only its digest is stored and looked up.
+fn hash_token(token: &str) -> String {
+    to_hex(&Sha256::digest(token.as_bytes()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn generate_random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    to_hex(&bytes)
+}
+
+fn sign_access_token(email: &str, role: &str, config: &AppConfig) -> Result<String, AppError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock is before the unix epoch");
+
+    let claims = Claims {
+        sub: email.to_string(),
+        role: role.to_string(),
+        iat: now.as_secs() as usize,
+        exp: (now + config.access_token_ttl()).as_secs() as usize,
+    };
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(config.jwt_secret.as_ref()),
+    )
+    .map_err(|_| AppError::Hashing)
+}
+
+impl<'a> UserRepository for PostgresUserRepository<'a> {
+    fn create_user(&mut self, new_user: NewUserObject) -> Result<UserObject, AppError> {
+        use crate::schema::user::dsl::*;
+
+        let plaintext_password = new_user
+            .password_hash
+            .as_ref()
+            .ok_or_else(|| AppError::Validation("password is required".into()))?;
+        let hashed_password = hash_password(plaintext_password, self.config.bcrypt_cost)?;
+
+        let new_user = NewUserObject {
+            pseudo: new_user.pseudo.clone(),
+            email: new_user.email.clone(),
+            password_hash: Some(hashed_password),
+            role: Some(DEFAULT_USER_ROLE.to_string()),
+        };
+
+        diesel::insert_into(user)
+            .values(&new_user)
+            .returning((id, pseudo, email, password_hash, role, has_avatar))
+            .get_result::<UserObject>(self.conn)
+            .map_err(AppError::from)
+    }
+
+    fn get_user_by_id(&mut self, user_id: i32) -> Result<UserObject, AppError> {
+        use crate::schema::user::dsl::*;
+
+        user.filter(id.eq(user_id))
+            .select((id, pseudo, email, password_hash, role, has_avatar))
+            .first::<UserObject>(self.conn)
+            .map_err(AppError::from)
+    }
+
+    fn list_users(
+        &mut self,
+        pagination: Pagination,
+        filter: UserFilter,
+    ) -> Result<Page<UserObject>, AppError> {
+        use crate::schema::user::dsl::*;
+
+        let mut rows_query = user.into_boxed();
+        let mut count_query = user.into_boxed();
+
+        if let Some(pseudo_filter) = &filter.pseudo {
+            let pattern = format!("%{}%", pseudo_filter);
+            rows_query = rows_query.filter(pseudo.ilike(pattern.clone()));
+            count_query = count_query.filter(pseudo.ilike(pattern));
+        }
+
+        if let Some(email_filter) = &filter.email {
+            let pattern = format!("%{}%", email_filter);
+            rows_query = rows_query.filter(email.ilike(pattern.clone()));
+            count_query = count_query.filter(email.ilike(pattern));
+        }
+
+        let total_count = count_query.count().get_result::<i64>(self.conn)?;
+
+        let items = rows_query
+            .select((id, pseudo, email, password_hash, role, has_avatar))
+            .limit(pagination.limit)
+            .offset(pagination.offset)
+            .load::<UserObject>(self.conn)?;
+
+        Ok(Page::new(items, total_count, pagination))
+    }
+
+    fn get_user_by_email(&mut self, p_email: &str) -> Result<UserObject, AppError> {
+        use crate::schema::user::dsl::*;
+
+        user.filter(email.eq(p_email))
+            .select((id, pseudo, email, password_hash, role, has_avatar))
+            .first::<UserObject>(self.conn)
+            .map_err(AppError::from)
+    }
+
+    fn delete_user(&mut self, user_id: i32) -> Result<usize, AppError> {
+        use crate::schema::user::dsl::*;
+
+        diesel::delete(user.filter(id.eq(user_id)))
+            .execute(self.conn)
+            .map_err(AppError::from)
+    }
+
+    fn update_user(
+        &mut self,
+        user_object: UserObject,
+        new_password: Option<&str>,
+    ) -> Result<UserObject, AppError> {
+        use crate::schema::user::dsl::*;
+
+        // `user_object.password_hash` is the digest already on file, loaded by
+        // the caller before applying edits - only re-hash when a new plaintext
+        // password actually came in, otherwise we'd bcrypt an already-hashed
+        // value and lock the account out.
+        let stored_password_hash = match new_password {
+            Some(plaintext) => hash_password(plaintext, self.config.bcrypt_cost)?,
+            None => user_object
+                .password_hash
+                .clone()
+                .ok_or_else(|| AppError::Validation("password is required".into()))?,
+        };
+
+        diesel::update(user.filter(id.eq(user_object.id)))
+            .set((
+                pseudo.eq(user_object.pseudo.as_ref()),
+                email.eq(user_object.email.as_ref()),
+                password_hash.eq(Some(stored_password_hash)),
+                role.eq(user_object.role.as_ref()),
+            ))
+            .returning((id, pseudo, email, password_hash, role, has_avatar))
+            .get_result::<UserObject>(self.conn)
+            .map_err(AppError::from)
+    }
+
+    fn login(&mut self, p_email: &str, p_password: &str) -> Result<LoginResponse, AppError> {
+        use crate::schema::user::dsl::*;
+
+        let user_object = user
+            .filter(email.eq(p_email))
+            .select((id, pseudo, email, password_hash, role, has_avatar))
+            .first::<UserObject>(self.conn)
+            .optional()?
+            .ok_or(AppError::Unauthorized)?;
+
+        let password_matches = bcrypt::verify(
+            p_password,
+            user_object
+                .password_hash
+                .as_ref()
+                .ok_or(AppError::Unauthorized)?,
+        )
+        .map_err(|_| AppError::Hashing)?;
+
+        if !password_matches {
+            return Err(AppError::Unauthorized);
+        }
+
+        let user_role = user_object.role.clone().unwrap_or_default();
+        let access_token = sign_access_token(
+            user_object.email.as_ref().ok_or(AppError::Unauthorized)?,
+            &user_role,
+            self.config,
+        )?;
+
+        let refresh_token_value = generate_random_token();
+        let expires_at = SystemTime::now() + REFRESH_TOKEN_TTL;
+
+        {
+            use crate::schema::refresh_token::dsl as refresh_token_dsl;
+
+            diesel::insert_into(refresh_token_dsl::refresh_token)
+                .values((
+                    refresh_token_dsl::user_id.eq(user_object.id),
+                    refresh_token_dsl::token_hash.eq(hash_token(&refresh_token_value)),
+                    refresh_token_dsl::expires_at.eq(expires_at),
+                ))
+                .execute(self.conn)?;
+        }
+
+        Ok(LoginResponse {
+            access_token,
+            refresh_token: refresh_token_value,
+        })
+    }
+
+    fn refresh_access_token(&mut self, p_refresh_token: &str) -> Result<String, AppError> {
+        use crate::schema::refresh_token::dsl as refresh_token_dsl;
+        use crate::schema::user::dsl as user_dsl;
+
+        let (token_user_id, expires_at, is_revoked) = refresh_token_dsl::refresh_token
+            .filter(refresh_token_dsl::token_hash.eq(hash_token(p_refresh_token)))
+            .select((
+                refresh_token_dsl::user_id,
+                refresh_token_dsl::expires_at,
+                refresh_token_dsl::revoked,
+            ))
+            .first::<(i32, SystemTime, bool)>(self.conn)
+            .optional()?
+            .ok_or(AppError::Unauthorized)?;
+
+        if is_revoked || expires_at < SystemTime::now() {
+            return Err(AppError::Unauthorized);
+        }
+
+        let user_object = user_dsl::user
+            .filter(user_dsl::id.eq(token_user_id))
+            .select((
+                user_dsl::id,
+                user_dsl::pseudo,
+                user_dsl::email,
+                user_dsl::password_hash,
+                user_dsl::role,
+                user_dsl::has_avatar,
+            ))
+            .first::<UserObject>(self.conn)?;
+
+        let user_role = user_object.role.clone().unwrap_or_default();
+        sign_access_token(
+            user_object.email.as_ref().ok_or(AppError::Unauthorized)?,
+            &user_role,
+            self.config,
+        )
+    }
+
+    fn logout(&mut self, p_refresh_token: &str) -> Result<(), AppError> {
+        use crate::schema::refresh_token::dsl::*;
+
+        diesel::update(refresh_token.filter(token_hash.eq(hash_token(p_refresh_token))))
+            .set(revoked.eq(true))
+            .execute(self.conn)?;
+
+        Ok(())
+    }
+
+    fn request_password_reset(&mut self, p_email: &str) -> Result<(), AppError> {
+        use crate::schema::password_reset_token::dsl as reset_dsl;
+        use crate::schema::user::dsl as user_dsl;
+
+        let target_user_id = user_dsl::user
+            .filter(user_dsl::email.eq(p_email))
+            .select(user_dsl::id)
+            .first::<i32>(self.conn)
+            .optional()?;
+
+        // Always behave as if the email existed so callers can't tell a
+        // registered address from an unregistered one.
+        let Some(target_user_id) = target_user_id else {
+            return Ok(());
+        };
+
+        let raw_token = generate_random_token();
+        let expires_at = SystemTime::now() + PASSWORD_RESET_TOKEN_TTL;
+
+        diesel::insert_into(reset_dsl::password_reset_token)
+            .values((
+                reset_dsl::user_id.eq(target_user_id),
+                reset_dsl::token_hash.eq(hash_token(&raw_token)),
+                reset_dsl::expires_at.eq(expires_at),
+            ))
+            .execute(self.conn)?;
+
+        // TODO: deliver `raw_token` to the user by email instead of discarding it.
+        Ok(())
+    }
+
+    fn reset_password(&mut self, p_token: &str, p_new_password: &str) -> Result<(), AppError> {
+        use crate::schema::password_reset_token::dsl as reset_dsl;
+        use crate::schema::user::dsl as user_dsl;
+
+        let incoming_hash = hash_token(p_token);
+
+        let (reset_token_id, target_user_id, expires_at, is_consumed) =
+            reset_dsl::password_reset_token
+                .filter(reset_dsl::token_hash.eq(&incoming_hash))
+                .select((
+                    reset_dsl::id,
+                    reset_dsl::user_id,
+                    reset_dsl::expires_at,
+                    reset_dsl::consumed,
+                ))
+                .first::<(i32, i32, SystemTime, bool)>(self.conn)?;
+
+        if is_consumed || expires_at < SystemTime::now() {
+            return Err(AppError::NotFound);
+        }
+
+        let hashed_password = hash_password(p_new_password, self.config.bcrypt_cost)?;
+
+        self.conn
+            .transaction::<_, Error, _>(|conn| {
+                diesel::update(user_dsl::user.filter(user_dsl::id.eq(target_user_id)))
+                    .set(user_dsl::password_hash.eq(Some(hashed_password)))
+                    .execute(conn)?;
+
+                diesel::update(
+                    reset_dsl::password_reset_token.filter(reset_dsl::id.eq(reset_token_id)),
+                )
+                .set(reset_dsl::consumed.eq(true))
+                .execute(conn)?;
+
+                Ok(())
+            })
+            .map_err(AppError::from)
+    }
+}
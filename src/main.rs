@@ -0,0 +1,84 @@
+mod application;
+mod config;
+mod domain;
+mod infrastructure;
+mod middleware;
+mod routes;
+mod schema;
+
+use actix_web::{web, App, HttpServer};
+use config::AppConfig;
+use diesel::r2d2::{self, ConnectionManager};
+use diesel::PgConnection;
+use dotenvy::dotenv;
+use std::env;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        routes::user::get_user_handler,
+        routes::user::create_user_handler,
+        routes::user::list_users_handler,
+        routes::user::delete_user_handler,
+        routes::user::update_user_handler,
+        routes::user::login_user_handler,
+        routes::user::refresh_user_handler,
+        routes::user::logout_user_handler,
+        routes::user::forgot_password_handler,
+        routes::user::reset_password_handler,
+        routes::user::upload_avatar_handler,
+        routes::user::get_avatar_handler,
+    ),
+    components(schemas(
+        domain::models::UserObject,
+        domain::models::NewUserObject,
+        domain::models::Page<domain::models::UserObject>,
+        domain::models::LoginRequest,
+        domain::models::LoginResponse,
+        domain::models::RefreshRequest,
+        domain::models::RefreshResponse,
+        domain::models::LogoutRequest,
+        domain::models::ForgotPasswordRequest,
+        domain::models::ResetPasswordRequest,
+    ))
+)]
+struct ApiDoc;
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    dotenv().ok();
+
+    let config_path = env::var("CONFIG_PATH").unwrap_or_else(|_| "config.toml".to_string());
+    let config = AppConfig::load(&config_path).unwrap_or_else(|err| {
+        eprintln!("invalid configuration: {err}");
+        std::process::exit(1);
+    });
+
+    let manager = ConnectionManager::<PgConnection>::new(config.database_url.clone());
+    let pool = r2d2::Pool::builder()
+        .max_size(config.db_pool_max_size)
+        .build(manager)
+        .expect("Failed to create database pool");
+
+    {
+        let mut conn = pool.get().expect("Failed to get a connection from the pool");
+        infrastructure::persistence::seed::seed_admin_role(&mut conn)
+            .expect("Failed to seed the admin role");
+    }
+
+    let openapi = ApiDoc::openapi();
+    let config = web::Data::new(config);
+
+    HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(config.clone())
+            .service(SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi.clone()))
+            .configure(routes::user::init)
+    })
+    .bind(("0.0.0.0", 8080))?
+    .run()
+    .await
+}
@@ -0,0 +1,47 @@
+use crate::config::AppConfig;
+use crate::domain::error::AppError;
+use crate::domain::models::Claims;
+use crate::domain::repository::RoleRepository;
+use crate::infrastructure::persistence::role_repository::PostgresRoleRepository;
+use actix_web::{web, HttpRequest};
+use diesel::r2d2::{self, ConnectionManager};
+use diesel::PgConnection;
+use jsonwebtoken::{decode, DecodingKey, Validation};
+
+/// Decodes the bearer JWT on `req` and checks that `permission` is in the
+/// role carried by its claims. Returns `AppError::Unauthorized`/`Forbidden`-
+/// shaped errors if the token is missing/invalid or the permission isn't
+/// granted, so callers can run this as the first line of a handler before
+/// touching the service. The role lives in the token itself, so this never
+/// needs to hit the database to find out who the caller is.
+pub fn require_permission(
+    req: &HttpRequest,
+    pool: &web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    config: &web::Data<AppConfig>,
+    permission: &str,
+) -> Result<(), AppError> {
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .ok_or(AppError::Unauthorized)?;
+
+    let claims = decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(config.jwt_secret.as_ref()),
+        &Validation::default(),
+    )
+    .map_err(|_| AppError::Unauthorized)?
+    .claims;
+
+    let mut conn = pool.get().map_err(|_| AppError::Internal)?;
+    let mut role_repo = PostgresRoleRepository { conn: &mut conn };
+    let permissions = role_repo.get_permissions_for_role(&claims.role)?;
+
+    if permissions.iter().any(|granted| granted == permission) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden)
+    }
+}
@@ -1,30 +1,67 @@
-use crate::application::services::UserService;
-use crate::domain::models::{NewUserObject, UserObject};
+use crate::application::services::{AvatarService, UserService};
+use crate::config::AppConfig;
+use crate::domain::error::AppError;
+use crate::domain::models::{
+    ForgotPasswordRequest, LoginRequest, LoginResponse, LogoutRequest, NewUserObject, Page,
+    Pagination, RefreshRequest, RefreshResponse, ResetPasswordRequest, UserFilter, UserObject,
+};
+use crate::infrastructure::persistence::avatar_repository::PostgresAvatarRepository;
 use crate::infrastructure::persistence::user_repository::PostgresUserRepository;
-use actix_web::{web, Error, HttpResponse, Responder};
+use crate::middleware::auth_guard::require_permission;
+use actix_multipart::Multipart;
+use actix_web::{web, HttpRequest, HttpResponse};
 use diesel::r2d2::{self, ConnectionManager};
 use diesel::PgConnection;
+use futures_util::TryStreamExt;
+use serde::Deserialize;
+
+/// Hard cap on the raw bytes read from an avatar upload, enforced while
+/// buffering the multipart body - well before the bytes ever reach the
+/// image decoder.
+const MAX_AVATAR_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct ListUsersQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub pseudo: Option<String>,
+    pub email: Option<String>,
+}
 
 fn with_user_service<F>(
     pool: &web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    config: &web::Data<AppConfig>,
     f: F,
-) -> Result<HttpResponse, Error>
+) -> Result<HttpResponse, AppError>
 where
-    F: FnOnce(&mut UserService<PostgresUserRepository>) -> Result<HttpResponse, Error>,
+    F: FnOnce(&mut UserService<PostgresUserRepository>) -> Result<HttpResponse, AppError>,
 {
-    let mut conn = pool.get().map_err(|e| {
-        actix_web::error::ErrorInternalServerError(format!(
-            "Failed to get a connection from the pool: {}",
-            e
-        ))
-    })?;
-
-    let mut user_repo = PostgresUserRepository { conn: &mut conn };
+    let mut conn = pool.get().map_err(|_| AppError::Internal)?;
+
+    let mut user_repo = PostgresUserRepository {
+        conn: &mut conn,
+        config: config.get_ref(),
+    };
     let mut user_service = UserService::new(&mut user_repo);
 
     f(&mut user_service)
 }
 
+fn with_avatar_service<F>(
+    pool: &web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    f: F,
+) -> Result<HttpResponse, AppError>
+where
+    F: FnOnce(&mut AvatarService<PostgresAvatarRepository>) -> Result<HttpResponse, AppError>,
+{
+    let mut conn = pool.get().map_err(|_| AppError::Internal)?;
+
+    let mut avatar_repo = PostgresAvatarRepository { conn: &mut conn };
+    let mut avatar_service = AvatarService::new(&mut avatar_repo);
+
+    f(&mut avatar_service)
+}
+
 #[utoipa::path(
     get,
     path = "/user/get/{user_id}",
@@ -39,13 +76,12 @@ where
 )]
 pub async fn get_user_handler(
     pool: web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    config: web::Data<AppConfig>,
     user_id: web::Path<i32>,
-) -> impl Responder {
-    with_user_service(&pool, |user_service| {
-        match user_service.get_user_by_id(*user_id) {
-            Ok(user) => Ok(HttpResponse::Ok().json(user)),
-            Err(_) => Ok(HttpResponse::NotFound().json("User not found!")),
-        }
+) -> Result<HttpResponse, AppError> {
+    with_user_service(&pool, &config, |user_service| {
+        let user = user_service.get_user_by_id(*user_id)?;
+        Ok(HttpResponse::Ok().json(user))
     })
 }
 
@@ -55,37 +91,53 @@ pub async fn get_user_handler(
     request_body = NewUserObject,
     responses(
         (status = 200, description = "User created successfully", body = UserObject),
-        (status = 500, description = "Failed to create user")
+        (status = 409, description = "A user with that email already exists")
     ),
     tag = "Users"
 )]
 pub async fn create_user_handler(
     pool: web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    config: web::Data<AppConfig>,
     query: web::Json<NewUserObject>,
-) -> impl Responder {
-    with_user_service(&pool, |user_service| {
-        match user_service.create_user(query.into_inner()) {
-            Ok(user) => Ok(HttpResponse::Ok().json(user)),
-            Err(_) => Ok(HttpResponse::InternalServerError().json("Failed to create user!")),
-        }
+) -> Result<HttpResponse, AppError> {
+    with_user_service(&pool, &config, |user_service| {
+        let user = user_service.create_user(query.into_inner())?;
+        Ok(HttpResponse::Ok().json(user))
     })
 }
 
 #[utoipa::path(
     get,
     path = "/user/list",
+    params(
+        ("limit" = Option<i64>, Query, description = "Max rows to return (default 20, capped at 100)"),
+        ("offset" = Option<i64>, Query, description = "Rows to skip (default 0)"),
+        ("pseudo" = Option<String>, Query, description = "Case-insensitive substring filter on pseudo"),
+        ("email" = Option<String>, Query, description = "Case-insensitive substring filter on email"),
+    ),
     responses(
-        (status = 200, description = "List of users retrieved successfully", body = Vec<UserObject>),
-        (status = 500, description = "Failed to retrieve users")
+        (status = 200, description = "Page of users retrieved successfully", body = Page<UserObject>),
+        (status = 403, description = "Caller lacks the user.list permission")
     ),
     tag = "Users"
 )]
 pub async fn list_users_handler(
+    req: HttpRequest,
     pool: web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
-) -> impl Responder {
-    with_user_service(&pool, |user_service| match user_service.get_all_users() {
-        Ok(users) => Ok(HttpResponse::Ok().json(users)),
-        Err(_) => Ok(HttpResponse::InternalServerError().json("Failed to get users!")),
+    config: web::Data<AppConfig>,
+    query: web::Query<ListUsersQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&req, &pool, &config, "user.list")?;
+
+    let pagination = Pagination::new(query.limit, query.offset);
+    let filter = UserFilter {
+        pseudo: query.pseudo.clone(),
+        email: query.email.clone(),
+    };
+
+    with_user_service(&pool, &config, |user_service| {
+        let page = user_service.list_users(pagination, filter)?;
+        Ok(HttpResponse::Ok().json(page))
     })
 }
 
@@ -94,7 +146,7 @@ pub async fn list_users_handler(
     path = "/user/delete/{user_id}",
     responses(
         (status = 200, description = "User deleted successfully"),
-        (status = 500, description = "Failed to delete user")
+        (status = 403, description = "Caller lacks the user.delete permission")
     ),
     params(
         ("user_id" = i32, Path, description = "User ID to delete")
@@ -102,14 +154,16 @@ pub async fn list_users_handler(
     tag = "Users"
 )]
 pub async fn delete_user_handler(
+    req: HttpRequest,
     pool: web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    config: web::Data<AppConfig>,
     user_id: web::Path<i32>,
-) -> impl Responder {
-    with_user_service(&pool, |user_service| {
-        match user_service.delete_user(*user_id) {
-            Ok(_) => Ok(HttpResponse::Ok().json("User deleted!")),
-            Err(_) => Ok(HttpResponse::InternalServerError().json("Failed to delete user!")),
-        }
+) -> Result<HttpResponse, AppError> {
+    require_permission(&req, &pool, &config, "user.delete")?;
+
+    with_user_service(&pool, &config, |user_service| {
+        user_service.delete_user(*user_id)?;
+        Ok(HttpResponse::Ok().json("User deleted!"))
     })
 }
 
@@ -119,7 +173,7 @@ pub async fn delete_user_handler(
     request_body = NewUserObject,
     responses(
         (status = 200, description = "User updated successfully", body = UserObject),
-        (status = 500, description = "Failed to update user")
+        (status = 404, description = "User not found")
     ),
     params(
         ("user_id" = i32, Path, description = "User ID to update")
@@ -128,11 +182,12 @@ pub async fn delete_user_handler(
 )]
 pub async fn update_user_handler(
     pool: web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    config: web::Data<AppConfig>,
     user_id: web::Path<i32>,
     query: web::Json<NewUserObject>,
-) -> impl Responder {
-    with_user_service(&pool, |user_service| {
-        let mut user_to_update = user_service.get_user_by_id(*user_id).unwrap();
+) -> Result<HttpResponse, AppError> {
+    with_user_service(&pool, &config, |user_service| {
+        let mut user_to_update = user_service.get_user_by_id(*user_id)?;
 
         // Update the user with the new values if they are provided
         if let Some(pseudo) = &query.pseudo {
@@ -141,17 +196,199 @@ pub async fn update_user_handler(
         if let Some(email) = &query.email {
             user_to_update.email = Some(email.clone());
         }
-        if let Some(password_hash) = &query.password_hash {
-            user_to_update.password_hash = Some(password_hash.clone());
-        }
         if let Some(role) = &query.role {
             user_to_update.role = Some(role.clone());
         }
 
-        match user_service.update_user(user_to_update) {
-            Ok(user) => Ok(HttpResponse::Ok().json(user)),
-            Err(_) => Ok(HttpResponse::InternalServerError().json("Failed to update user!")),
+        // `query.password_hash` is actually the new plaintext password, if any -
+        // pass it through separately so the repository only re-hashes when a
+        // new password was actually supplied, instead of re-hashing the digest
+        // already loaded onto `user_to_update`.
+        let user = user_service.update_user(user_to_update, query.password_hash.as_deref())?;
+        Ok(HttpResponse::Ok().json(user))
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/user/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Login successful", body = LoginResponse),
+        (status = 401, description = "Invalid email or password")
+    ),
+    tag = "Users"
+)]
+pub async fn login_user_handler(
+    pool: web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    config: web::Data<AppConfig>,
+    query: web::Json<LoginRequest>,
+) -> Result<HttpResponse, AppError> {
+    with_user_service(&pool, &config, |user_service| {
+        let tokens = user_service.login(&query.email, &query.password)?;
+        Ok(HttpResponse::Ok().json(tokens))
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/user/refresh",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "Access token refreshed", body = RefreshResponse),
+        (status = 401, description = "Refresh token is invalid, expired or revoked")
+    ),
+    tag = "Users"
+)]
+pub async fn refresh_user_handler(
+    pool: web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    config: web::Data<AppConfig>,
+    query: web::Json<RefreshRequest>,
+) -> Result<HttpResponse, AppError> {
+    with_user_service(&pool, &config, |user_service| {
+        let access_token = user_service.refresh_access_token(&query.refresh_token)?;
+        Ok(HttpResponse::Ok().json(RefreshResponse { access_token }))
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/user/logout",
+    request_body = LogoutRequest,
+    responses(
+        (status = 200, description = "Refresh token revoked"),
+    ),
+    tag = "Users"
+)]
+pub async fn logout_user_handler(
+    pool: web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    config: web::Data<AppConfig>,
+    query: web::Json<LogoutRequest>,
+) -> Result<HttpResponse, AppError> {
+    with_user_service(&pool, &config, |user_service| {
+        user_service.logout(&query.refresh_token)?;
+        Ok(HttpResponse::Ok().json("Logged out"))
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/user/forgot-password",
+    request_body = ForgotPasswordRequest,
+    responses(
+        (status = 200, description = "A reset token was issued if the email is registered"),
+    ),
+    tag = "Users"
+)]
+pub async fn forgot_password_handler(
+    pool: web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    config: web::Data<AppConfig>,
+    query: web::Json<ForgotPasswordRequest>,
+) -> Result<HttpResponse, AppError> {
+    with_user_service(&pool, &config, |user_service| {
+        // Always respond 200 regardless of the outcome, so the response
+        // can't be used to tell whether the email is registered.
+        let _ = user_service.request_password_reset(&query.email);
+        Ok(HttpResponse::Ok().json("If that email is registered, a reset token was issued"))
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/user/reset-password",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 200, description = "Password reset successfully"),
+        (status = 404, description = "Reset token is invalid, expired or already used")
+    ),
+    tag = "Users"
+)]
+pub async fn reset_password_handler(
+    pool: web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    config: web::Data<AppConfig>,
+    query: web::Json<ResetPasswordRequest>,
+) -> Result<HttpResponse, AppError> {
+    with_user_service(&pool, &config, |user_service| {
+        user_service.reset_password(&query.token, &query.new_password)?;
+        Ok(HttpResponse::Ok().json("Password reset successfully"))
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/user/{user_id}/avatar",
+    request_body(content_type = "multipart/form-data", description = "Form with a single image file field"),
+    responses(
+        (status = 200, description = "Avatar updated successfully"),
+        (status = 400, description = "File is not a decodable image, or exceeds the size limit"),
+        (status = 403, description = "Caller lacks the user.avatar.write permission")
+    ),
+    params(
+        ("user_id" = i32, Path, description = "User ID to attach the avatar to")
+    ),
+    tag = "Users"
+)]
+pub async fn upload_avatar_handler(
+    req: HttpRequest,
+    pool: web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    config: web::Data<AppConfig>,
+    user_id: web::Path<i32>,
+    mut payload: Multipart,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&req, &pool, &config, "user.avatar.write")?;
+
+    let mut raw_image = Vec::new();
+
+    while let Some(mut field) = payload
+        .try_next()
+        .await
+        .map_err(|_| AppError::Validation("malformed multipart body".into()))?
+    {
+        while let Some(chunk) = field
+            .try_next()
+            .await
+            .map_err(|_| AppError::Validation("malformed multipart body".into()))?
+        {
+            if raw_image.len() + chunk.len() > MAX_AVATAR_UPLOAD_BYTES {
+                return Err(AppError::Validation(format!(
+                    "avatar upload must not exceed {MAX_AVATAR_UPLOAD_BYTES} bytes"
+                )));
+            }
+            raw_image.extend_from_slice(&chunk);
         }
+    }
+
+    if raw_image.is_empty() {
+        return Err(AppError::Validation("no image file provided".into()));
+    }
+
+    with_avatar_service(&pool, |avatar_service| {
+        avatar_service.upload_avatar(*user_id, &raw_image)?;
+        Ok(HttpResponse::Ok().json("Avatar updated"))
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/user/{user_id}/avatar",
+    responses(
+        (status = 200, description = "Avatar image bytes", content_type = "image/png"),
+        (status = 404, description = "User has no avatar")
+    ),
+    params(
+        ("user_id" = i32, Path, description = "User ID whose avatar to fetch")
+    ),
+    tag = "Users"
+)]
+pub async fn get_avatar_handler(
+    pool: web::Data<r2d2::Pool<ConnectionManager<PgConnection>>>,
+    user_id: web::Path<i32>,
+) -> Result<HttpResponse, AppError> {
+    with_avatar_service(&pool, |avatar_service| {
+        let (image_data, avatar_content_type) = avatar_service.get_avatar(*user_id)?;
+        Ok(HttpResponse::Ok()
+            .content_type(avatar_content_type)
+            .body(image_data))
     })
 }
 
@@ -163,12 +400,15 @@ pub fn init(cfg: &mut web::ServiceConfig) {
             .route("/get/{user_id}", web::get().to(get_user_handler)) // GET /user/get
             .route("/list", web::get().to(list_users_handler)) // GET /user/list
             .route("/delete/{user_id}", web::delete().to(delete_user_handler)) // DELETE /user/delete
-            .route("/update/{user_id}", web::put().to(update_user_handler)), // PUT /user/update
+            .route("/update/{user_id}", web::put().to(update_user_handler)) // PUT /user/update
+            .route("/login", web::post().to(login_user_handler)) // POST /user/login
+            .route("/refresh", web::post().to(refresh_user_handler)) // POST /user/refresh
+            .route("/logout", web::post().to(logout_user_handler)) // POST /user/logout
+            .route("/forgot-password", web::post().to(forgot_password_handler)) // POST /user/forgot-password
+            .route("/reset-password", web::post().to(reset_password_handler)) // POST /user/reset-password
+            .route("/{user_id}/avatar", web::post().to(upload_avatar_handler)) // POST /user/{user_id}/avatar
+            .route("/{user_id}/avatar", web::get().to(get_avatar_handler)), // GET /user/{user_id}/avatar
 
-                                                                             //.route("/login", web::post().to(login_user_handler)) // POST /user/login
-                                                                             //.route("/logout", web::post().to(logout_user_handler)) // POST /user/logout
-                                                                             //.route("/forgot-password", web::post().to(forgot_password_handler)) // POST /user/forgot-password
-                                                                             //.route("/reset-password", web::post().to(reset_password_handler)) // POST /user/reset-password
                                                                              //.route("/report", web::get().to(report_user_handler)) // GET /user/report
 
                                                                              // sub-scope for role related routes
@@ -177,4 +417,4 @@ pub fn init(cfg: &mut web::ServiceConfig) {
                                                                              //        .route("/get", web::get().to(get_user_role_handler)) // GET /user/role/get
                                                                              //        .route("/set", web::post().to(set_user_role_handler)), // POST /user/role/set
     );
-}
\ No newline at end of file
+}